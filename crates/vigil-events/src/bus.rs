@@ -0,0 +1,188 @@
+//! Daemon event bus and subscription server.
+//!
+//! The daemon owns an [`EventBus`]: a task holding a registry of connected
+//! clients that each carry an optional per-type `filter`. Processed events from
+//! [`EventPipeline::subscribe`](crate::EventPipeline::subscribe) are pumped
+//! into the bus via [`EventBus::pump`] and fanned out — but only to clients
+//! whose filter matches, so uninterested subscribers never see the event. The
+//! registry periodically prunes clients whose receiver has been dropped.
+//!
+//! [`serve`] exposes the bus over a Unix domain socket speaking
+//! newline-delimited JSON, which `vigil events watch` connects to.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, error, info, warn};
+
+use crate::Event;
+
+/// Default path of the daemon's subscription socket.
+pub const DEFAULT_SOCKET: &str = "/run/vigil/vigil.sock";
+
+/// Control messages driving the bus registry task.
+pub enum SystemEvent {
+    /// Register a new client with an optional event-type filter.
+    Subscribe {
+        tx: mpsc::Sender<Event>,
+        filter: Option<String>,
+    },
+    /// Broadcast an event to every matching client.
+    Emit(Event),
+    /// Prune clients whose receiver has been dropped.
+    Ping,
+}
+
+/// Frame a client sends first to open a subscription.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubscribeFrame {
+    /// Only forward events whose `event_type` equals this value; `None` for all.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+/// Handle to the daemon event bus. Cloneable; all clones share one registry.
+#[derive(Clone)]
+pub struct EventBus {
+    control: mpsc::Sender<SystemEvent>,
+}
+
+struct Client {
+    tx: mpsc::Sender<Event>,
+    filter: Option<String>,
+}
+
+impl Client {
+    fn wants(&self, event: &Event) -> bool {
+        match &self.filter {
+            Some(ty) => &event.event_type == ty,
+            None => true,
+        }
+    }
+}
+
+impl EventBus {
+    /// Spawn the registry task and return a handle to it. `ping_interval`
+    /// controls how often dead subscribers are pruned.
+    pub fn new(ping_interval: Duration) -> Self {
+        let (control, mut rx) = mpsc::channel::<SystemEvent>(1024);
+        let bus = Self {
+            control: control.clone(),
+        };
+
+        tokio::spawn(async move {
+            let mut clients: Vec<Client> = Vec::new();
+            while let Some(msg) = rx.recv().await {
+                match msg {
+                    SystemEvent::Subscribe { tx, filter } => {
+                        debug!("new subscriber (filter: {:?})", filter);
+                        clients.push(Client { tx, filter });
+                    }
+                    SystemEvent::Emit(event) => {
+                        // Apply each client's filter before forwarding, and
+                        // drop any client whose receiver is gone.
+                        clients.retain(|c| {
+                            if !c.wants(&event) {
+                                return !c.tx.is_closed();
+                            }
+                            c.tx.try_send(event.clone()).is_ok() || !c.tx.is_closed()
+                        });
+                    }
+                    SystemEvent::Ping => {
+                        clients.retain(|c| !c.tx.is_closed());
+                    }
+                }
+            }
+        });
+
+        // Periodically prune dead subscribers.
+        let pinger = control;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(ping_interval);
+            loop {
+                ticker.tick().await;
+                if pinger.send(SystemEvent::Ping).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        bus
+    }
+
+    /// Emit an event to all matching subscribers.
+    pub async fn emit(&self, event: Event) {
+        if let Err(e) = self.control.send(SystemEvent::Emit(event)).await {
+            error!("event bus closed: {}", e);
+        }
+    }
+
+    /// Register a subscriber and return the receiving half of its channel.
+    pub async fn subscribe(&self, filter: Option<String>) -> mpsc::Receiver<Event> {
+        let (tx, rx) = mpsc::channel(1024);
+        let _ = self.control.send(SystemEvent::Subscribe { tx, filter }).await;
+        rx
+    }
+
+    /// Drive the bus from the pipeline's broadcast receiver: every processed
+    /// event flows into the registry for fan-out.
+    pub async fn pump(&self, mut rx: broadcast::Receiver<Event>) {
+        loop {
+            match rx.recv().await {
+                Ok(event) => self.emit(event).await,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("event bus pump lagged, skipped {} events", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Serve the bus over a Unix domain socket at `path`, speaking newline-
+/// delimited JSON. Each connection sends a [`SubscribeFrame`] line, then
+/// receives one JSON-encoded [`Event`] per line.
+pub async fn serve(bus: EventBus, path: impl AsRef<Path>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    // A stale socket from a previous run would block binding.
+    let _ = tokio::fs::remove_file(path).await;
+
+    let listener = UnixListener::bind(path)?;
+    info!("subscription socket listening at {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let bus = bus.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(bus, stream).await {
+                debug!("subscriber disconnected: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_client(bus: EventBus, stream: UnixStream) -> std::io::Result<()> {
+    let (read_half, mut write_half) = stream.into_split();
+    let mut lines = BufReader::new(read_half).lines();
+
+    // The first line carries the subscription filter.
+    let frame = match lines.next_line().await? {
+        Some(line) => serde_json::from_str::<SubscribeFrame>(&line).unwrap_or_default(),
+        None => return Ok(()),
+    };
+
+    let mut events = bus.subscribe(frame.filter).await;
+    while let Some(event) = events.recv().await {
+        let mut json = serde_json::to_vec(&event).unwrap_or_default();
+        json.push(b'\n');
+        write_half.write_all(&json).await?;
+    }
+    Ok(())
+}
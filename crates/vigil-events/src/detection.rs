@@ -0,0 +1,621 @@
+//! Sigma-style detection rules compiled into an [`EventProcessor`].
+//!
+//! A [`Rule`] is authored in YAML with a `detection` map of named *selections*
+//! — each a set of field/value matches — plus a boolean `condition` expression
+//! combining those names with `and`/`or`/`not` and parentheses. Compiling a
+//! rule type-checks it ([`CompiledRule::compile`]): every selection named in
+//! the condition must exist, every field path must resolve to a supported
+//! type, and numeric/string operators must agree with that type. Diagnostics
+//! carry the [`Location`] of the offending node so authors get a precise
+//! pointer rather than a generic parse failure.
+//!
+//! At runtime [`DetectionProcessor`] evaluates every compiled rule against an
+//! event's flattened fields and, on a match, attaches an alert (rule id and
+//! severity) to the event's `data`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Event, EventError, EventProcessor};
+
+/// Severity assigned to a rule and carried into the alert on a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// A detection rule as authored in YAML, before compilation.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    /// Named selections plus the `condition` entry combining them.
+    pub detection: HashMap<String, serde_yaml::Value>,
+}
+
+/// Source location of a node, for diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// An error produced while parsing or type-checking a rule.
+#[derive(Debug, Error)]
+pub enum RuleError {
+    #[error("failed to parse rule: {0}")]
+    Parse(#[from] serde_yaml::Error),
+
+    #[error("{message} (at {location})")]
+    Invalid { message: String, location: Location },
+
+    #[error("invalid condition: {0}")]
+    Condition(String),
+}
+
+impl RuleError {
+    fn invalid(message: impl Into<String>, location: Location) -> Self {
+        RuleError::Invalid {
+            message: message.into(),
+            location,
+        }
+    }
+}
+
+/// The static type of a supported field, used to validate operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Str,
+    Uint,
+    /// An arbitrary JSON path under `data.`; type is only known at runtime.
+    Dynamic,
+}
+
+/// Resolve the static type of a field path, or `None` if unsupported.
+fn field_type(path: &str) -> Option<FieldType> {
+    match path {
+        "event_type" | "process.comm" | "process.exe" => Some(FieldType::Str),
+        "process.uid" => Some(FieldType::Uint),
+        _ if path.starts_with("data.") => Some(FieldType::Dynamic),
+        _ => None,
+    }
+}
+
+/// Comparison operator, parsed from a `field|modifier` key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Gt,
+    Lt,
+    Gte,
+    Lte,
+}
+
+impl Op {
+    fn parse(modifier: &str) -> Option<Op> {
+        Some(match modifier {
+            "contains" => Op::Contains,
+            "startswith" => Op::StartsWith,
+            "endswith" => Op::EndsWith,
+            "gt" => Op::Gt,
+            "lt" => Op::Lt,
+            "gte" => Op::Gte,
+            "lte" => Op::Lte,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, Op::Gt | Op::Lt | Op::Gte | Op::Lte)
+    }
+
+    fn is_string(self) -> bool {
+        matches!(self, Op::Contains | Op::StartsWith | Op::EndsWith)
+    }
+}
+
+/// A single `field op value` comparison within a selection.
+#[derive(Debug, Clone)]
+struct FieldMatch {
+    path: String,
+    op: Op,
+    /// Any of these values satisfies the match (a scalar becomes a one-element
+    /// list).
+    values: Vec<serde_yaml::Value>,
+}
+
+/// Boolean combination of selection names.
+#[derive(Debug, Clone)]
+enum Cond {
+    Selection(String),
+    Not(Box<Cond>),
+    And(Box<Cond>, Box<Cond>),
+    Or(Box<Cond>, Box<Cond>),
+}
+
+/// A rule that has been parsed, type-checked and is ready to evaluate.
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    selections: HashMap<String, Vec<FieldMatch>>,
+    condition: Cond,
+}
+
+impl CompiledRule {
+    /// Parse YAML and compile + type-check it. `source` is the original text,
+    /// used to locate offending nodes in diagnostics.
+    pub fn compile(source: &str) -> Result<CompiledRule, RuleError> {
+        let rule: Rule = serde_yaml::from_str(source)?;
+
+        let condition_raw = rule
+            .detection
+            .get("condition")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| RuleError::Condition("missing `condition` entry".into()))?;
+        let condition = parse_condition(condition_raw)?;
+
+        // Compile each selection (everything in `detection` except `condition`).
+        let mut selections = HashMap::new();
+        for (name, value) in &rule.detection {
+            if name == "condition" {
+                continue;
+            }
+            selections.insert(name.clone(), compile_selection(name, value, source)?);
+        }
+
+        // Every selection named in the condition must exist.
+        for name in condition_names(&condition) {
+            if !selections.contains_key(&name) {
+                return Err(RuleError::invalid(
+                    format!("condition references unknown selection `{name}`"),
+                    locate(source, &name),
+                ));
+            }
+        }
+
+        Ok(CompiledRule {
+            id: rule.id,
+            title: rule.title,
+            severity: rule.severity,
+            selections,
+            condition,
+        })
+    }
+
+    /// A human-readable listing of the rule's fields, for `rules list
+    /// --detailed`.
+    pub fn describe(&self) -> String {
+        let mut out = format!("{} [{:?}] {}", self.id, self.severity, self.title);
+        let mut names: Vec<_> = self.selections.keys().collect();
+        names.sort();
+        for name in names {
+            for m in &self.selections[name] {
+                out.push_str(&format!("\n    {name}: {} {:?} {:?}", m.path, m.op, m.values));
+            }
+        }
+        out
+    }
+
+    fn matches(&self, event: &Event) -> bool {
+        eval(&self.condition, &self.selections, event)
+    }
+}
+
+/// Compile one selection's field matches, type-checking each against its field.
+fn compile_selection(
+    name: &str,
+    value: &serde_yaml::Value,
+    source: &str,
+) -> Result<Vec<FieldMatch>, RuleError> {
+    let map = value.as_mapping().ok_or_else(|| {
+        RuleError::invalid(
+            format!("selection `{name}` must be a mapping of field: value"),
+            locate(source, name),
+        )
+    })?;
+
+    let mut matches = Vec::with_capacity(map.len());
+    for (key, val) in map {
+        let key = key.as_str().ok_or_else(|| {
+            RuleError::invalid(
+                format!("selection `{name}` has a non-string field key"),
+                locate(source, name),
+            )
+        })?;
+
+        // Split `field|modifier`.
+        let (path, op) = match key.split_once('|') {
+            Some((path, modifier)) => {
+                let op = Op::parse(modifier).ok_or_else(|| {
+                    RuleError::invalid(
+                        format!("unknown operator `{modifier}` on field `{path}`"),
+                        locate(source, key),
+                    )
+                })?;
+                (path, op)
+            }
+            None => (key, Op::Eq),
+        };
+
+        let ty = field_type(path).ok_or_else(|| {
+            RuleError::invalid(format!("unsupported field `{path}`"), locate(source, key))
+        })?;
+
+        // Operator must agree with the field's static type. Dynamic (`data.`)
+        // paths accept any operator since their type is only known at runtime.
+        if ty != FieldType::Dynamic {
+            if op.is_numeric() && ty != FieldType::Uint {
+                return Err(RuleError::invalid(
+                    format!("numeric operator on non-numeric field `{path}`"),
+                    locate(source, key),
+                ));
+            }
+            if op.is_string() && ty != FieldType::Str {
+                return Err(RuleError::invalid(
+                    format!("string operator on non-string field `{path}`"),
+                    locate(source, key),
+                ));
+            }
+        }
+
+        let values = match val {
+            serde_yaml::Value::Sequence(seq) => seq.clone(),
+            other => vec![other.clone()],
+        };
+
+        matches.push(FieldMatch {
+            path: path.to_string(),
+            op,
+            values,
+        });
+    }
+
+    Ok(matches)
+}
+
+/// Best-effort location of `needle` in the source, for diagnostics.
+fn locate(source: &str, needle: &str) -> Location {
+    for (i, line) in source.lines().enumerate() {
+        if let Some(col) = line.find(needle) {
+            return Location {
+                line: i + 1,
+                column: col + 1,
+            };
+        }
+    }
+    Location { line: 1, column: 1 }
+}
+
+// --- condition expression ---------------------------------------------------
+
+fn condition_names(cond: &Cond) -> Vec<String> {
+    let mut out = Vec::new();
+    collect_names(cond, &mut out);
+    out
+}
+
+fn collect_names(cond: &Cond, out: &mut Vec<String>) {
+    match cond {
+        Cond::Selection(name) => out.push(name.clone()),
+        Cond::Not(inner) => collect_names(inner, out),
+        Cond::And(a, b) | Cond::Or(a, b) => {
+            collect_names(a, out);
+            collect_names(b, out);
+        }
+    }
+}
+
+/// Recursive-descent parser for `sel and not sel2 or (sel3)`.
+fn parse_condition(input: &str) -> Result<Cond, RuleError> {
+    let tokens = tokenize_condition(input);
+    let mut parser = CondParser { tokens, pos: 0 };
+    let cond = parser.parse_or()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(RuleError::Condition(format!(
+            "unexpected token `{}`",
+            parser.tokens[parser.pos]
+        )));
+    }
+    Ok(cond)
+}
+
+fn tokenize_condition(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut cur = String::new();
+    for ch in input.chars() {
+        match ch {
+            '(' | ')' => {
+                if !cur.is_empty() {
+                    tokens.push(std::mem::take(&mut cur));
+                }
+                tokens.push(ch.to_string());
+            }
+            c if c.is_whitespace() => {
+                if !cur.is_empty() {
+                    tokens.push(std::mem::take(&mut cur));
+                }
+            }
+            c => cur.push(c),
+        }
+    }
+    if !cur.is_empty() {
+        tokens.push(cur);
+    }
+    tokens
+}
+
+struct CondParser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl CondParser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|s| s.as_str())
+    }
+
+    fn bump(&mut self) -> Option<String> {
+        let t = self.tokens.get(self.pos).cloned();
+        if t.is_some() {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn parse_or(&mut self) -> Result<Cond, RuleError> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some("or") {
+            self.bump();
+            let right = self.parse_and()?;
+            left = Cond::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Cond, RuleError> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some("and") {
+            self.bump();
+            let right = self.parse_unary()?;
+            left = Cond::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Cond, RuleError> {
+        if self.peek() == Some("not") {
+            self.bump();
+            return Ok(Cond::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<Cond, RuleError> {
+        match self.bump() {
+            Some(ref t) if t == "(" => {
+                let inner = self.parse_or()?;
+                match self.bump() {
+                    Some(ref close) if close == ")" => Ok(inner),
+                    _ => Err(RuleError::Condition("missing closing `)`".into())),
+                }
+            }
+            Some(ref t) if t == ")" || t == "and" || t == "or" || t == "not" => {
+                Err(RuleError::Condition(format!("unexpected token `{t}`")))
+            }
+            Some(name) => Ok(Cond::Selection(name)),
+            None => Err(RuleError::Condition("unexpected end of condition".into())),
+        }
+    }
+}
+
+// --- evaluation -------------------------------------------------------------
+
+fn eval(cond: &Cond, selections: &HashMap<String, Vec<FieldMatch>>, event: &Event) -> bool {
+    match cond {
+        Cond::Selection(name) => selections
+            .get(name)
+            .map(|matches| matches.iter().all(|m| match_field(m, event)))
+            .unwrap_or(false),
+        Cond::Not(inner) => !eval(inner, selections, event),
+        Cond::And(a, b) => eval(a, selections, event) && eval(b, selections, event),
+        Cond::Or(a, b) => eval(a, selections, event) || eval(b, selections, event),
+    }
+}
+
+/// Resolve a field path against the event's flattened fields.
+fn resolve<'a>(path: &str, event: &'a Event) -> Option<FieldValue<'a>> {
+    match path {
+        "event_type" => Some(FieldValue::Str(&event.event_type)),
+        "process.comm" => Some(FieldValue::Str(&event.process.comm)),
+        "process.exe" => Some(FieldValue::Str(&event.process.exe)),
+        "process.uid" => Some(FieldValue::Uint(event.process.uid as u64)),
+        _ => {
+            let rest = path.strip_prefix("data.")?;
+            let mut node = &event.data;
+            for segment in rest.split('.') {
+                node = node.get(segment)?;
+            }
+            Some(FieldValue::Json(node))
+        }
+    }
+}
+
+enum FieldValue<'a> {
+    Str(&'a str),
+    Uint(u64),
+    Json(&'a serde_json::Value),
+}
+
+fn match_field(m: &FieldMatch, event: &Event) -> bool {
+    let Some(actual) = resolve(&m.path, event) else {
+        return false;
+    };
+    m.values.iter().any(|expected| compare(&actual, m.op, expected))
+}
+
+fn compare(actual: &FieldValue, op: Op, expected: &serde_yaml::Value) -> bool {
+    match actual {
+        FieldValue::Str(s) => match_str(s, op, expected),
+        FieldValue::Uint(n) => match_uint(*n, op, expected),
+        FieldValue::Json(v) => match_json(v, op, expected),
+    }
+}
+
+fn match_str(actual: &str, op: Op, expected: &serde_yaml::Value) -> bool {
+    let Some(exp) = expected.as_str() else {
+        return false;
+    };
+    match op {
+        Op::Eq => actual == exp,
+        Op::Contains => actual.contains(exp),
+        Op::StartsWith => actual.starts_with(exp),
+        Op::EndsWith => actual.ends_with(exp),
+        _ => false,
+    }
+}
+
+fn match_uint(actual: u64, op: Op, expected: &serde_yaml::Value) -> bool {
+    let Some(exp) = expected.as_u64() else {
+        return false;
+    };
+    match op {
+        Op::Eq => actual == exp,
+        Op::Gt => actual > exp,
+        Op::Lt => actual < exp,
+        Op::Gte => actual >= exp,
+        Op::Lte => actual <= exp,
+        _ => false,
+    }
+}
+
+fn match_json(actual: &serde_json::Value, op: Op, expected: &serde_yaml::Value) -> bool {
+    match actual {
+        serde_json::Value::String(s) => match_str(s, op, expected),
+        serde_json::Value::Number(n) => match n.as_u64() {
+            Some(u) => match_uint(u, op, expected),
+            None => false,
+        },
+        serde_json::Value::Bool(b) => expected.as_bool() == Some(*b) && op == Op::Eq,
+        _ => false,
+    }
+}
+
+/// An [`EventProcessor`] that evaluates a set of compiled rules and annotates
+/// matching events with an alert.
+pub struct DetectionProcessor {
+    rules: Vec<CompiledRule>,
+}
+
+impl DetectionProcessor {
+    pub fn new(rules: Vec<CompiledRule>) -> Self {
+        Self { rules }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventProcessor for DetectionProcessor {
+    async fn process(&self, event: &Event) -> Result<Option<Event>, EventError> {
+        let alerts: Vec<serde_json::Value> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(event))
+            .map(|rule| {
+                serde_json::json!({
+                    "rule_id": rule.id,
+                    "severity": rule.severity,
+                })
+            })
+            .collect();
+
+        if alerts.is_empty() {
+            return Ok(Some(event.clone()));
+        }
+
+        let mut event = event.clone();
+        if let serde_json::Value::Object(map) = &mut event.data {
+            map.insert("alerts".into(), serde_json::Value::Array(alerts));
+        } else {
+            event.data = serde_json::json!({ "alerts": alerts });
+        }
+        Ok(Some(event))
+    }
+
+    fn name(&self) -> &str {
+        "detection"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProcessInfo;
+
+    fn event(comm: &str, uid: u32) -> Event {
+        Event {
+            id: "e1".into(),
+            timestamp: 1,
+            event_type: "process_exec".into(),
+            process: ProcessInfo {
+                pid: 10,
+                ppid: 1,
+                uid,
+                gid: 0,
+                comm: comm.into(),
+                exe: "/bin/bash".into(),
+                start_time: 0,
+            },
+            data: serde_json::json!({}),
+        }
+    }
+
+    const RULE: &str = "id: shell-as-root\n\
+        title: Shell spawned by root\n\
+        severity: high\n\
+        detection:\n  \
+          selection:\n    \
+            event_type: process_exec\n    \
+            process.comm: bash\n  \
+          privileged:\n    \
+            process.uid: 0\n  \
+          condition: selection and privileged\n";
+
+    #[test]
+    fn compiles_and_matches() {
+        let rule = CompiledRule::compile(RULE).unwrap();
+        assert!(rule.matches(&event("bash", 0)));
+        assert!(!rule.matches(&event("bash", 1000)));
+        assert!(!rule.matches(&event("zsh", 0)));
+    }
+
+    #[test]
+    fn rejects_unknown_selection_in_condition() {
+        let src = RULE.replace("selection and privileged", "selection and missing");
+        let err = CompiledRule::compile(&src).unwrap_err();
+        assert!(matches!(err, RuleError::Invalid { .. }));
+    }
+
+    #[test]
+    fn rejects_numeric_operator_on_string_field() {
+        let src = RULE.replace("process.comm: bash", "process.comm|gt: bash");
+        let err = CompiledRule::compile(&src).unwrap_err();
+        assert!(matches!(err, RuleError::Invalid { .. }));
+    }
+}
@@ -1,8 +1,17 @@
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::sync::{broadcast, mpsc};
-use tracing::{debug, error, info};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+pub mod bus;
+pub mod detection;
+pub mod ring;
+pub mod response;
+pub mod storage;
 
 /// Represents a system event captured by Vigil
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +40,11 @@ pub struct ProcessInfo {
     pub gid: u32,
     pub comm: String,
     pub exe: String,
+
+    /// Process start time in clock ticks since boot (`/proc/<pid>/stat` field
+    /// 22). Captured so a later response can tell the original process from a
+    /// recycled pid that re-execs the same `exe`.
+    pub start_time: u64,
 }
 
 #[derive(Error, Debug)]
@@ -56,6 +70,13 @@ pub trait EventProcessor: Send + Sync {
 
 #[async_trait::async_trait]
 pub trait EventCollector: Send + Sync {
+    /// Hand the collector the producing half of its ring buffer.
+    ///
+    /// Called once by the pipeline before [`start`](Self::start). The collector
+    /// retains the [`Producer`](ring::Producer) and pushes captured events into
+    /// it from its hot path without locking.
+    fn attach(&mut self, producer: ring::Producer);
+
     /// Start collecting events
     async fn start(&mut self) -> Result<(), EventError>;
 
@@ -108,32 +129,115 @@ impl Default for EventConfig {
     }
 }
 
+/// Backoff policy governing how the supervisor restarts failed workers.
+#[derive(Debug, Clone)]
+pub struct BackoffConfig {
+    /// Delay after the first failure.
+    pub base: Duration,
+    /// Maximum delay; the exponential backoff saturates here.
+    pub cap: Duration,
+    /// A worker that runs at least this long is considered healthy and its
+    /// backoff resets to `base`.
+    pub reset_after: Duration,
+    /// Consecutive failures of a single worker at which it is abandoned and the
+    /// pipeline is marked degraded (so at most `max_attempts - 1` restarts).
+    pub max_attempts: u32,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(100),
+            cap: Duration::from_secs(30),
+            reset_after: Duration::from_secs(60),
+            max_attempts: 10,
+        }
+    }
+}
+
+/// Runtime health snapshot of a pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Status {
+    /// Total events discarded because a collector's ring buffer was full.
+    pub dropped_events: u64,
+    /// True once a worker has exhausted its restart budget or lost its ring.
+    pub degraded: bool,
+}
+
 pub struct EventPipeline {
     config: EventConfig,
+    backoff: BackoffConfig,
     collectors: Vec<Box<dyn EventCollector>>,
     processors: Vec<Arc<dyn EventProcessor>>,
     storage: Arc<dyn EventStorage>,
     event_tx: broadcast::Sender<Event>,
-    shutdown_tx: mpsc::Sender<()>,
+    /// Shutdown signal observed by every worker's `select!` loop.
+    shutdown_tx: watch::Sender<bool>,
+    /// Supervisor task handles, one per ring, retained so [`stop`](Self::stop)
+    /// can await a clean, drained exit.
+    supervisors: Vec<JoinHandle<()>>,
+    /// Per-ring dropped-event counters, kept so [`status`](Self::status) can
+    /// report drops after the producers have moved into their collectors.
+    dropped_counters: Vec<Arc<AtomicU64>>,
+    /// Set when any worker exhausts its restart budget.
+    degraded: Arc<AtomicBool>,
+    /// Consuming halves of the collector rings, taken by the supervisors in
+    /// [`start`](Self::start).
+    consumers: Vec<ring::Consumer>,
 }
 
 impl EventPipeline {
     pub fn new(
         config: EventConfig,
-        collectors: Vec<Box<dyn EventCollector>>,
+        mut collectors: Vec<Box<dyn EventCollector>>,
         processors: Vec<Arc<dyn EventProcessor>>,
         storage: Arc<dyn EventStorage>,
     ) -> Self {
         let (event_tx, _) = broadcast::channel(config.buffer_size);
-        let (shutdown_tx, _) = mpsc::channel(1);
+        let (shutdown_tx, _) = watch::channel(false);
+
+        // Build one SPSC ring per collector: the producer goes to the
+        // collector's hot path, the consumer stays here for draining.
+        let mut dropped_counters = Vec::with_capacity(collectors.len());
+        let mut consumers = Vec::with_capacity(collectors.len());
+        for collector in &mut collectors {
+            let (producer, consumer) = ring::ring(config.buffer_size);
+            dropped_counters.push(consumer.dropped_counter());
+            consumers.push(consumer);
+            collector.attach(producer);
+        }
 
         Self {
             config,
+            backoff: BackoffConfig::default(),
             collectors,
             processors,
             storage,
             event_tx,
             shutdown_tx,
+            supervisors: Vec::new(),
+            dropped_counters,
+            degraded: Arc::new(AtomicBool::new(false)),
+            consumers,
+        }
+    }
+
+    /// Override the default worker-restart backoff policy.
+    pub fn with_backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Current health snapshot, aggregating drops across every collector ring.
+    pub fn status(&self) -> Status {
+        let dropped_events = self
+            .dropped_counters
+            .iter()
+            .map(|c| c.load(Ordering::Relaxed))
+            .sum();
+        Status {
+            dropped_events,
+            degraded: self.degraded.load(Ordering::Relaxed),
         }
     }
 
@@ -155,8 +259,16 @@ impl EventPipeline {
     pub async fn stop(&mut self) -> Result<(), EventError> {
         info!("Stopping event pipeline");
 
-        if let Err(e) = self.shutdown_tx.send(()).await {
-            error!("Failed to send shutdown signal: {}", e);
+        // Signal every worker to drain in-flight batches and exit.
+        if self.shutdown_tx.send(true).is_err() {
+            error!("Failed to send shutdown signal: all workers already gone");
+        }
+
+        // Await each supervisor so shutdown actually reaches the loops.
+        for handle in self.supervisors.drain(..) {
+            if let Err(e) = handle.await {
+                error!("Worker supervisor ended abnormally: {}", e);
+            }
         }
 
         for collector in &mut self.collectors {
@@ -173,81 +285,200 @@ impl EventPipeline {
         self.event_tx.subscribe()
     }
 
-    async fn spawn_workers(&self) {
-        let event_tx = self.event_tx.clone();
-        let processors = self.processors.clone();
-        let storage = self.storage.clone();
+    async fn spawn_workers(&mut self) {
         let batch_size = self.config.batch_size;
 
-        for _ in 0..self.config.processor_parallelism {
-            let event_rx = self.event_tx.subscribe();
-            let processors = processors.clone();
-            let storage = storage.clone();
-            let event_tx = event_tx.clone();
-
-            tokio::spawn(async move {
-                Self::process_events(event_rx, processors, storage, event_tx, batch_size).await;
-            });
+        // One supervised worker per ring: the single-consumer invariant means
+        // each consumer is owned by exactly one worker and needs no locking.
+        for consumer in std::mem::take(&mut self.consumers) {
+            let processors = self.processors.clone();
+            let storage = self.storage.clone();
+            let event_tx = self.event_tx.clone();
+            let backoff = self.backoff.clone();
+            let degraded = self.degraded.clone();
+            let shutdown = self.shutdown_tx.subscribe();
+
+            self.supervisors.push(tokio::spawn(async move {
+                supervise(
+                    consumer, processors, storage, event_tx, batch_size, backoff, degraded,
+                    shutdown,
+                )
+                .await;
+            }));
         }
     }
+}
 
-    async fn process_events(
-        mut event_rx: broadcast::Receiver<Event>,
-        processors: Vec<Arc<dyn EventProcessor>>,
-        storage: Arc<dyn EventStorage>,
-        event_tx: broadcast::Sender<Event>,
-        batch_size: usize,
-    ) {
-        let mut batch = Vec::with_capacity(batch_size);
-
-        while let Ok(event) = event_rx.recv().await {
-            let mut current_event = event.clone();
-
-            // Apply all processors in sequence
-            for processor in &processors {
-                match processor.process(&current_event).await {
-                    Ok(Some(processed_event)) => {
-                        current_event = processed_event;
-                    }
-                    Ok(None) => {
-                        debug!("Event filtered by processor {}", processor.name());
-                        continue;
-                    }
-                    Err(e) => {
-                        error!("Error processing event: {}", e);
-                        continue;
-                    }
+/// Exponential-backoff-with-jitter supervisor for a single ring worker.
+///
+/// Runs [`drain_ring`] to completion; on a clean (shutdown) exit it returns. On
+/// a worker error it restarts after a jittered delay that doubles each
+/// consecutive failure up to `cap`, resetting to `base` once a worker has run
+/// healthily for `reset_after`. After `max_attempts` consecutive failures the
+/// ring is abandoned and the pipeline is marked degraded.
+async fn supervise(
+    mut consumer: ring::Consumer,
+    processors: Vec<Arc<dyn EventProcessor>>,
+    storage: Arc<dyn EventStorage>,
+    event_tx: broadcast::Sender<Event>,
+    batch_size: usize,
+    backoff: BackoffConfig,
+    degraded: Arc<AtomicBool>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut delay = backoff.base;
+    let mut attempts = 0u32;
+
+    loop {
+        let started = Instant::now();
+        let result = drain_ring(
+            &mut consumer,
+            &processors,
+            &storage,
+            &event_tx,
+            batch_size,
+            &mut shutdown,
+        )
+        .await;
+
+        match result {
+            Ok(()) => return, // clean shutdown
+            Err(e) => {
+                // A worker that ran healthily for a while resets its backoff.
+                if started.elapsed() >= backoff.reset_after {
+                    delay = backoff.base;
+                    attempts = 0;
                 }
-            }
 
-            // Add to batch
-            batch.push(current_event.clone());
+                attempts += 1;
+                if attempts >= backoff.max_attempts {
+                    error!("worker abandoned after {} consecutive failures: {}", attempts, e);
+                    degraded.store(true, Ordering::Relaxed);
+                    return;
+                }
+
+                let wait = jitter(delay);
+                warn!(
+                    "worker failed ({}); restart {}/{} in {:?}",
+                    e, attempts, backoff.max_attempts, wait
+                );
+
+                // Respect shutdown even while backing off.
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {}
+                    _ = shutdown.changed() => return,
+                }
 
-            // Forward processed event
-            if let Err(e) = event_tx.send(current_event) {
-                error!("Failed to forward processed event: {}", e);
+                delay = (delay * 2).min(backoff.cap);
             }
+        }
+    }
+}
+
+/// Apply +/-25% jitter to a backoff delay to avoid synchronized restarts.
+fn jitter(delay: Duration) -> Duration {
+    let nanos = delay.as_nanos() as u64;
+    if nanos == 0 {
+        return delay;
+    }
+    let span = nanos / 2; // full jitter window is 50% of the delay
+    // Derive a pseudo-random offset from the current time without a dependency.
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let offset = seed % span.max(1);
+    Duration::from_nanos(nanos - span / 2 + offset)
+}
 
-            // Store batch if full
-            if batch.len() >= batch_size {
-                Self::store_batch(&storage, &batch).await;
-                batch.clear();
+/// Drain a single ring until shutdown (returns `Ok`) or a fatal worker error
+/// (returns `Err`, leaving the consumer reusable for a restart).
+async fn drain_ring(
+    consumer: &mut ring::Consumer,
+    processors: &[Arc<dyn EventProcessor>],
+    storage: &Arc<dyn EventStorage>,
+    event_tx: &broadcast::Sender<Event>,
+    batch_size: usize,
+    shutdown: &mut watch::Receiver<bool>,
+) -> Result<(), EventError> {
+    // The ring has no async wakeup, so poll it, backing off briefly when empty
+    // to avoid a busy spin while still observing the shutdown signal.
+    let idle = Duration::from_millis(1);
+
+    loop {
+        if *shutdown.borrow() {
+            // Drain whatever is still buffered before exiting cleanly.
+            drain_remaining(consumer, processors, storage, event_tx, batch_size).await?;
+            return Ok(());
+        }
+
+        let events = consumer.read_batch(batch_size);
+        if events.is_empty() {
+            tokio::select! {
+                _ = tokio::time::sleep(idle) => continue,
+                _ = shutdown.changed() => continue,
             }
         }
 
-        // Store any remaining events
-        if !batch.is_empty() {
-            Self::store_batch(&storage, &batch).await;
+        process_batch(events, processors, storage, event_tx).await?;
+    }
+}
+
+/// Read and process any buffered events one last time.
+async fn drain_remaining(
+    consumer: &mut ring::Consumer,
+    processors: &[Arc<dyn EventProcessor>],
+    storage: &Arc<dyn EventStorage>,
+    event_tx: &broadcast::Sender<Event>,
+    batch_size: usize,
+) -> Result<(), EventError> {
+    loop {
+        let events = consumer.read_batch(batch_size);
+        if events.is_empty() {
+            return Ok(());
         }
+        process_batch(events, processors, storage, event_tx).await?;
     }
+}
 
-    async fn store_batch(storage: &Arc<dyn EventStorage>, batch: &[Event]) {
-        for event in batch {
-            if let Err(e) = storage.store(event).await {
-                error!("Failed to store event: {}", e);
+async fn process_batch(
+    events: Vec<Event>,
+    processors: &[Arc<dyn EventProcessor>],
+    storage: &Arc<dyn EventStorage>,
+    event_tx: &broadcast::Sender<Event>,
+) -> Result<(), EventError> {
+    let mut batch = Vec::with_capacity(events.len());
+    'events: for event in events {
+        let mut current_event = event;
+
+        // Apply all processors in sequence.
+        for processor in processors {
+            match processor.process(&current_event).await {
+                Ok(Some(processed_event)) => current_event = processed_event,
+                Ok(None) => {
+                    debug!("Event filtered by processor {}", processor.name());
+                    continue 'events;
+                }
+                Err(e) => {
+                    error!("Error processing event: {}", e);
+                    continue;
+                }
             }
         }
+
+        batch.push(current_event.clone());
+
+        // Notify downstream subscribers of the processed event.
+        if let Err(e) = event_tx.send(current_event) {
+            debug!("No active subscribers for processed event: {}", e);
+        }
+    }
+
+    // Propagate storage failures so the supervisor can restart this worker.
+    for event in &batch {
+        storage.store(event).await?;
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -272,6 +503,7 @@ mod tests {
                 gid: 1000,
                 comm: "test".into(),
                 exe: "/bin/test".into(),
+                start_time: 0,
             },
             data: serde_json::json!({"test": true}),
         };
@@ -0,0 +1,375 @@
+//! Automated response actions triggered by detection alerts.
+//!
+//! A [`DetectionProcessor`](crate::detection::DetectionProcessor) annotates
+//! matching events with an `alerts` array; the [`ResponseEngine`] consumes
+//! those annotated events and runs a configured [`ResponseAction`] per matched
+//! rule, chosen by the rule's severity. The modes mirror a busy-update policy:
+//!
+//! * [`AlertOnly`] — log/emit only.
+//! * [`SignalAction`] — send a configurable signal (e.g. `SIGSTOP`) to suspend
+//!   the offending process.
+//! * [`KillAction`] — `SIGKILL` the process and, optionally, its descendants
+//!   discovered through the `ppid` chain.
+//! * [`IsolateAction`] — install a firewall deny rule for the process owner.
+//!
+//! Actions are idempotent (a burst of matching events for the same pid acts
+//! once) and verify that the target's `exe` and start-time still match before
+//! signaling, so a recycled pid is never hit. Every action is surfaced as a new [`Event`]
+//! (`response_kill`, `response_signal`, …) emitted into a sink so it flows
+//! through storage and the watch stream.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::detection::Severity;
+use crate::{Event, EventError, EventProcessor, ProcessInfo};
+
+#[derive(Debug, Error)]
+pub enum ResponseError {
+    #[error("process {0} no longer matches the target; skipping to avoid hitting a recycled pid")]
+    Recycled(i32),
+
+    #[error("failed to signal process {pid}: {source}")]
+    Signal { pid: i32, source: nix::Error },
+
+    #[error("failed to isolate process {pid}: {message}")]
+    Isolate { pid: i32, message: String },
+}
+
+/// An action taken against an offending process.
+pub trait ResponseAction: Send + Sync {
+    /// Apply the action to `target`, returning a JSON description of what was
+    /// done for the surfaced response event.
+    fn apply(&self, target: &ProcessInfo) -> Result<serde_json::Value, ResponseError>;
+
+    /// The `event_type` of the response event this action surfaces.
+    fn event_type(&self) -> &'static str;
+}
+
+/// Log/emit only — takes no action against the process.
+pub struct AlertOnly;
+
+impl ResponseAction for AlertOnly {
+    fn apply(&self, target: &ProcessInfo) -> Result<serde_json::Value, ResponseError> {
+        Ok(serde_json::json!({ "pid": target.pid }))
+    }
+
+    fn event_type(&self) -> &'static str {
+        "response_alert"
+    }
+}
+
+/// Send a configurable signal (default `SIGSTOP`) to suspend the process.
+pub struct SignalAction {
+    pub signal: Signal,
+}
+
+impl ResponseAction for SignalAction {
+    fn apply(&self, target: &ProcessInfo) -> Result<serde_json::Value, ResponseError> {
+        signal::kill(Pid::from_raw(target.pid), self.signal)
+            .map_err(|source| ResponseError::Signal {
+                pid: target.pid,
+                source,
+            })?;
+        Ok(serde_json::json!({ "pid": target.pid, "signal": self.signal.as_str() }))
+    }
+
+    fn event_type(&self) -> &'static str {
+        "response_signal"
+    }
+}
+
+/// `SIGKILL` the process and, when `descendants` is set, its children via the
+/// `ppid` chain.
+pub struct KillAction {
+    pub descendants: bool,
+}
+
+impl ResponseAction for KillAction {
+    fn apply(&self, target: &ProcessInfo) -> Result<serde_json::Value, ResponseError> {
+        let mut killed = Vec::new();
+
+        // Kill descendants first so they cannot re-parent away during the kill.
+        if self.descendants {
+            for pid in descendants(target.pid) {
+                if signal::kill(Pid::from_raw(pid), Signal::SIGKILL).is_ok() {
+                    killed.push(pid);
+                }
+            }
+        }
+
+        signal::kill(Pid::from_raw(target.pid), Signal::SIGKILL).map_err(|source| {
+            ResponseError::Signal {
+                pid: target.pid,
+                source,
+            }
+        })?;
+        killed.push(target.pid);
+
+        Ok(serde_json::json!({ "pid": target.pid, "killed": killed }))
+    }
+
+    fn event_type(&self) -> &'static str {
+        "response_kill"
+    }
+}
+
+/// Install a firewall deny rule for the offending process's owner.
+pub struct IsolateAction;
+
+impl ResponseAction for IsolateAction {
+    fn apply(&self, target: &ProcessInfo) -> Result<serde_json::Value, ResponseError> {
+        // Drop the offending user's outbound traffic via the iptables owner
+        // match. The rule is scoped to the uid, not the pid: every process of
+        // that user loses outbound traffic; other users are not affected.
+        let status = std::process::Command::new("iptables")
+            .args([
+                "-A",
+                "OUTPUT",
+                "-m",
+                "owner",
+                "--uid-owner",
+                &target.uid.to_string(),
+                "-j",
+                "DROP",
+            ])
+            .status()
+            .map_err(|e| ResponseError::Isolate {
+                pid: target.pid,
+                message: e.to_string(),
+            })?;
+
+        if !status.success() {
+            return Err(ResponseError::Isolate {
+                pid: target.pid,
+                message: format!("iptables exited with {status}"),
+            });
+        }
+        Ok(serde_json::json!({ "pid": target.pid, "uid": target.uid }))
+    }
+
+    fn event_type(&self) -> &'static str {
+        "response_isolate"
+    }
+}
+
+/// How to respond to an alert, selected by rule severity.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "mode")]
+pub enum ResponseMode {
+    AlertOnly,
+    Signal {
+        /// Signal name, e.g. `SIGSTOP`. Defaults to `SIGSTOP`.
+        #[serde(default = "default_signal")]
+        signal: String,
+    },
+    Kill {
+        #[serde(default)]
+        descendants: bool,
+    },
+    Isolate,
+}
+
+fn default_signal() -> String {
+    "SIGSTOP".into()
+}
+
+impl ResponseMode {
+    fn into_action(self) -> Arc<dyn ResponseAction> {
+        match self {
+            ResponseMode::AlertOnly => Arc::new(AlertOnly),
+            ResponseMode::Signal { signal } => Arc::new(SignalAction {
+                signal: parse_signal(&signal),
+            }),
+            ResponseMode::Kill { descendants } => Arc::new(KillAction { descendants }),
+            ResponseMode::Isolate => Arc::new(IsolateAction),
+        }
+    }
+}
+
+fn parse_signal(name: &str) -> Signal {
+    name.parse().unwrap_or(Signal::SIGSTOP)
+}
+
+/// Verifies a target still refers to the same process before acting, guarding
+/// against recycled pids.
+pub trait ProcessVerifier: Send + Sync {
+    fn still_matches(&self, info: &ProcessInfo) -> bool;
+}
+
+/// Default verifier: the live `/proc/<pid>/exe` symlink must still resolve to
+/// the recorded executable path *and* the process start-time (`/proc/<pid>/stat`
+/// field 22) must match. The start-time check is what distinguishes a recycled
+/// pid that happens to re-exec the same binary (a respawned shell or daemon)
+/// from the original process.
+pub struct ProcVerifier;
+
+impl ProcessVerifier for ProcVerifier {
+    fn still_matches(&self, info: &ProcessInfo) -> bool {
+        let exe_ok = match std::fs::read_link(format!("/proc/{}/exe", info.pid)) {
+            Ok(path) => path.to_string_lossy() == info.exe,
+            Err(_) => false,
+        };
+        exe_ok && proc_start_time(info.pid) == Some(info.start_time)
+    }
+}
+
+/// Read a process's start-time (clock ticks since boot) from `/proc/<pid>/stat`
+/// field 22, returning `None` if the process is gone or the file is malformed.
+///
+/// Field 22 sits after `comm`, which may itself contain spaces and parentheses,
+/// so we split on the last `)` before counting space-separated fields.
+fn proc_start_time(pid: i32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let rest = &stat[stat.rfind(')')? + 1..];
+    // After `comm`, field 3 (state) is the first token; starttime is field 22,
+    // i.e. the 20th token of `rest`.
+    rest.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Tracks where a pid is in the response lifecycle, so a burst of same-pid
+/// events — possibly draining on different rings concurrently — acts once.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PidState {
+    /// An action is currently running for this pid.
+    InFlight,
+    /// An action has completed for this pid; never act again.
+    Done,
+}
+
+/// Consumes alert-annotated events and executes the configured response.
+pub struct ResponseEngine {
+    actions: HashMap<Severity, Arc<dyn ResponseAction>>,
+    verifier: Box<dyn ProcessVerifier>,
+    /// Response state per pid, so repeated matches do not re-signal a process.
+    actioned: Mutex<HashMap<i32, PidState>>,
+    /// Sink for surfaced response events.
+    sink: mpsc::UnboundedSender<Event>,
+}
+
+impl ResponseEngine {
+    /// Build an engine from a severity→mode config and an event sink. Response
+    /// events are emitted into `sink` so they flow through storage and watch.
+    pub fn new(
+        config: HashMap<Severity, ResponseMode>,
+        sink: mpsc::UnboundedSender<Event>,
+    ) -> Self {
+        Self::with_verifier(config, sink, Box::new(ProcVerifier))
+    }
+
+    pub fn with_verifier(
+        config: HashMap<Severity, ResponseMode>,
+        sink: mpsc::UnboundedSender<Event>,
+        verifier: Box<dyn ProcessVerifier>,
+    ) -> Self {
+        let actions = config
+            .into_iter()
+            .map(|(sev, mode)| (sev, mode.into_action()))
+            .collect();
+        Self {
+            actions,
+            verifier,
+            actioned: Mutex::new(HashMap::new()),
+            sink,
+        }
+    }
+
+    /// Handle one (possibly alert-carrying) event, executing at most one action
+    /// per pid and surfacing a response event for each action taken.
+    async fn handle(&self, event: &Event) {
+        let Some(alerts) = event.data.get("alerts").and_then(|a| a.as_array()) else {
+            return;
+        };
+
+        // The highest severity present drives the action.
+        let Some(severity) = alerts
+            .iter()
+            .filter_map(|a| a.get("severity").cloned())
+            .filter_map(|v| serde_json::from_value::<Severity>(v).ok())
+            .max_by_key(|s| *s as u8)
+        else {
+            return;
+        };
+
+        let Some(action) = self.actions.get(&severity) else {
+            return;
+        };
+
+        let pid = event.process.pid;
+
+        // Guard against recycled pids.
+        if !self.verifier.still_matches(&event.process) {
+            warn!("{}", ResponseError::Recycled(pid));
+            return;
+        }
+
+        // Idempotency under concurrency: claim the pid while holding the lock,
+        // before running `apply`. `ResponseEngine` is shared across the parallel
+        // drain workers, so two same-pid events on different rings must not both
+        // pass the check and both act. A claimed pid is marked `Done` on success
+        // and cleared on failure so a later legitimate match can retry.
+        {
+            let mut actioned = self.actioned.lock().unwrap();
+            if actioned.contains_key(&pid) {
+                return;
+            }
+            actioned.insert(pid, PidState::InFlight);
+        }
+
+        // `apply` may exec `iptables` or send signals — blocking work that must
+        // not stall the async runtime thread, so run it on the blocking pool.
+        let action = action.clone();
+        let event_type = action.event_type();
+        let target = event.process.clone();
+        let result = tokio::task::spawn_blocking(move || action.apply(&target)).await;
+
+        match result {
+            Ok(Ok(detail)) => {
+                self.actioned.lock().unwrap().insert(pid, PidState::Done);
+                info!("response {} taken on pid {}", event_type, pid);
+                self.emit(event_type, event, detail);
+            }
+            Ok(Err(e)) => {
+                self.actioned.lock().unwrap().remove(&pid);
+                error!("response action failed: {}", e);
+            }
+            Err(e) => {
+                self.actioned.lock().unwrap().remove(&pid);
+                error!("response action task failed: {}", e);
+            }
+        }
+    }
+
+    fn emit(&self, event_type: &str, source: &Event, detail: serde_json::Value) {
+        let response = Event {
+            id: format!("{}-{}", event_type, source.id),
+            timestamp: source.timestamp,
+            event_type: event_type.to_string(),
+            process: source.process.clone(),
+            data: detail,
+        };
+        if self.sink.send(response).is_err() {
+            warn!("response sink closed; dropping {} event", event_type);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventProcessor for ResponseEngine {
+    async fn process(&self, event: &Event) -> Result<Option<Event>, EventError> {
+        self.handle(event).await;
+        // Pass the alert event through unchanged; response events are surfaced
+        // separately via the sink.
+        Ok(Some(event.clone()))
+    }
+
+    fn name(&self) -> &str {
+        "response"
+    }
+}
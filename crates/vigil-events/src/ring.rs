@@ -0,0 +1,113 @@
+//! Lock-free SPSC ring buffers for the kernel→userspace event handoff.
+//!
+//! Each [`EventCollector`](crate::EventCollector) owns a single [`Producer`]
+//! and pushes events without locking; the pipeline owns the matching
+//! [`Consumer`] and drains batches on a dedicated task. Because every ring has
+//! exactly one producer and one consumer the implementation needs no locks —
+//! it wraps [`rtrb`]'s `Producer`/`Consumer` split. When the ring is full the
+//! producer does not block: it drops the event and bumps a shared atomic
+//! counter that the pipeline surfaces through [`Status`](crate::Status).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::Event;
+
+/// Create an SPSC ring sized for `capacity` events.
+///
+/// The returned [`Producer`]/[`Consumer`] pair shares a dropped-event counter;
+/// move the producer into the collector and keep the consumer in the pipeline.
+pub fn ring(capacity: usize) -> (Producer, Consumer) {
+    let (tx, rx) = rtrb::RingBuffer::new(capacity);
+    let dropped = Arc::new(AtomicU64::new(0));
+    (
+        Producer {
+            inner: tx,
+            dropped: dropped.clone(),
+        },
+        Consumer { inner: rx, dropped },
+    )
+}
+
+/// Producing half of an event ring, owned by a single collector.
+pub struct Producer {
+    inner: rtrb::Producer<Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Producer {
+    /// Push an event into the ring without blocking.
+    ///
+    /// Returns `true` if the event was enqueued. If the ring is full the event
+    /// is discarded and the shared `dropped` counter is incremented — the hot
+    /// path must never stall waiting on userspace.
+    pub fn push(&mut self, event: Event) -> bool {
+        match self.inner.push(event) {
+            Ok(()) => true,
+            Err(_) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    /// Number of events dropped because the ring was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Shared handle to the dropped-event counter, so the pipeline can report
+    /// it through [`Status`](crate::Status) after the producer has been moved
+    /// into a collector.
+    pub fn dropped_counter(&self) -> Arc<AtomicU64> {
+        self.dropped.clone()
+    }
+}
+
+/// Consuming half of an event ring, owned by the pipeline drain task.
+pub struct Consumer {
+    inner: rtrb::Consumer<Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Consumer {
+    /// Drain up to `max` events in a single batch, amortizing wakeups.
+    ///
+    /// Reads whatever is currently available (capped at `max`) via a single
+    /// `read_chunk`, returning an empty vector when the ring is empty.
+    pub fn read_batch(&mut self, max: usize) -> Vec<Event> {
+        let available = self.inner.slots().min(max);
+        if available == 0 {
+            return Vec::new();
+        }
+
+        match self.inner.read_chunk(available) {
+            Ok(chunk) => {
+                // Consuming the chunk by value `ptr::read`s each event out of
+                // its slot and commits as it goes, moving ownership into the
+                // batch. Cloning out of `as_slices()` and `commit_all()` would
+                // leave the originals to be overwritten without a drop, leaking
+                // every event's heap data.
+                let mut batch = Vec::with_capacity(available);
+                for event in chunk {
+                    batch.push(event);
+                }
+                batch
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Number of events dropped because the ring was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Shared handle to the dropped-event counter. The pipeline keeps this so
+    /// it can report drops through [`Status`](crate::Status) after the matching
+    /// producer has been moved into a collector — both halves share the same
+    /// `Arc`.
+    pub fn dropped_counter(&self) -> Arc<AtomicU64> {
+        self.dropped.clone()
+    }
+}
@@ -0,0 +1,260 @@
+//! SQLite-backed [`EventStorage`] with versioned schema migrations.
+//!
+//! Events are persisted to a single `events` table whose hot query columns
+//! (`timestamp`, `event_type`, `pid`, `comm`) are indexed, while the full
+//! event-specific payload is kept as a JSON `data` blob. A pooled connection
+//! (via [`r2d2`]) lets the async pipeline and blocking importer share one
+//! database, and an ordered migration list keyed off `PRAGMA user_version`
+//! brings an old file up to [`DB_VERSION`] on open.
+
+use std::path::Path;
+
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params, params_from_iter, types::Value};
+
+use crate::{Event, EventError, EventQuery, ProcessInfo};
+
+/// Current schema version. Equal to the number of entries in [`MIGRATIONS`].
+pub const DB_VERSION: i64 = 2;
+
+/// Ordered schema migrations. Entry `i` (0-based) upgrades the database from
+/// `user_version` `i` to `i + 1`; never reorder or edit an applied entry —
+/// append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    // v0 -> v1: initial events table.
+    "CREATE TABLE events (
+        id          TEXT PRIMARY KEY,
+        timestamp   INTEGER NOT NULL,
+        event_type  TEXT NOT NULL,
+        pid         INTEGER NOT NULL,
+        ppid        INTEGER NOT NULL,
+        uid         INTEGER NOT NULL,
+        gid         INTEGER NOT NULL,
+        comm        TEXT NOT NULL,
+        exe         TEXT NOT NULL,
+        data        TEXT NOT NULL
+    );
+    CREATE INDEX idx_events_timestamp ON events (timestamp);
+    CREATE INDEX idx_events_event_type ON events (event_type);
+    CREATE INDEX idx_events_pid ON events (pid);
+    CREATE INDEX idx_events_comm ON events (comm);",
+    // v1 -> v2: record process start-time so the response engine can tell a
+    // recycled pid from the original process.
+    "ALTER TABLE events ADD COLUMN start_time INTEGER NOT NULL DEFAULT 0;",
+];
+
+/// A pooled SQLite implementation of [`EventStorage`].
+#[derive(Clone)]
+pub struct SqliteStorage {
+    pool: Pool<SqliteConnectionManager>,
+}
+
+impl SqliteStorage {
+    /// Open (creating if absent) the database at `path` and run migrations.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EventError> {
+        let manager = SqliteConnectionManager::file(path);
+        Self::from_manager(manager, None)
+    }
+
+    /// Open an in-memory database, primarily for tests.
+    pub fn in_memory() -> Result<Self, EventError> {
+        // Each `:memory:` connection is a *distinct* database, so the pool must
+        // hand back the one connection migrations ran on — pin it to size 1.
+        Self::from_manager(SqliteConnectionManager::memory(), Some(1))
+    }
+
+    fn from_manager(
+        manager: SqliteConnectionManager,
+        max_size: Option<u32>,
+    ) -> Result<Self, EventError> {
+        // Every pooled connection enables WAL so readers and the several
+        // concurrent writers (one drain worker per ring plus the import thread)
+        // don't lock each other out, and a busy timeout so a writer waits for
+        // the lock instead of returning `SQLITE_BUSY` under burst load.
+        let manager = manager.with_init(|conn| {
+            conn.busy_timeout(std::time::Duration::from_secs(5))?;
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            Ok(())
+        });
+        let mut builder = Pool::builder();
+        if let Some(max) = max_size {
+            builder = builder.max_size(max);
+        }
+        let pool = builder
+            .build(manager)
+            .map_err(|e| EventError::StorageError(e.to_string()))?;
+        let storage = Self { pool };
+        storage.migrate()?;
+        Ok(storage)
+    }
+
+    /// Apply any migrations the open database is behind on, inside a single
+    /// transaction, then stamp the new `user_version`.
+    fn migrate(&self) -> Result<(), EventError> {
+        let mut conn = self.conn()?;
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(map_err)?;
+
+        if version >= DB_VERSION {
+            return Ok(());
+        }
+
+        let tx = conn.transaction().map_err(map_err)?;
+        for step in MIGRATIONS.iter().skip(version as usize) {
+            tx.execute_batch(step).map_err(map_err)?;
+        }
+        // user_version does not accept a bound parameter.
+        tx.execute_batch(&format!("PRAGMA user_version = {DB_VERSION}"))
+            .map_err(map_err)?;
+        tx.commit().map_err(map_err)?;
+        Ok(())
+    }
+
+    fn conn(&self) -> Result<r2d2::PooledConnection<SqliteConnectionManager>, EventError> {
+        self.pool.get().map_err(|e| EventError::StorageError(e.to_string()))
+    }
+
+    /// Insert a batch of events in one transaction. Used by the bulk importer
+    /// so DB work amortizes across `batch_size` rows.
+    pub fn store_batch(&self, events: &[Event]) -> Result<(), EventError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction().map_err(map_err)?;
+        {
+            let mut stmt = tx.prepare_cached(INSERT_SQL).map_err(map_err)?;
+            for event in events {
+                insert_event(&mut stmt, event)?;
+            }
+        }
+        tx.commit().map_err(map_err)
+    }
+}
+
+const INSERT_SQL: &str = "INSERT OR REPLACE INTO events \
+    (id, timestamp, event_type, pid, ppid, uid, gid, comm, exe, start_time, data) \
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
+
+fn insert_event(stmt: &mut rusqlite::CachedStatement, event: &Event) -> Result<(), EventError> {
+    let data = serde_json::to_string(&event.data).map_err(|e| map_err(e.into()))?;
+    stmt.execute(params![
+        event.id,
+        event.timestamp as i64,
+        event.event_type,
+        event.process.pid,
+        event.process.ppid,
+        event.process.uid,
+        event.process.gid,
+        event.process.comm,
+        event.process.exe,
+        event.process.start_time as i64,
+        data,
+    ])
+    .map_err(map_err)?;
+    Ok(())
+}
+
+/// Build the parametrized `SELECT` for an [`EventQuery`], returning the SQL and
+/// its positional bind values.
+fn build_query(query: &EventQuery) -> (String, Vec<Value>) {
+    let mut sql = String::from(
+        "SELECT id, timestamp, event_type, pid, ppid, uid, gid, comm, exe, start_time, data FROM events",
+    );
+    let mut clauses: Vec<String> = Vec::new();
+    let mut binds: Vec<Value> = Vec::new();
+
+    if let Some(start) = query.start_time {
+        binds.push(Value::Integer(start as i64));
+        clauses.push(format!("timestamp >= ?{}", binds.len()));
+    }
+    if let Some(end) = query.end_time {
+        binds.push(Value::Integer(end as i64));
+        clauses.push(format!("timestamp <= ?{}", binds.len()));
+    }
+    if let Some(types) = &query.event_types {
+        if !types.is_empty() {
+            let mut placeholders = Vec::with_capacity(types.len());
+            for ty in types {
+                binds.push(Value::Text(ty.clone()));
+                placeholders.push(format!("?{}", binds.len()));
+            }
+            clauses.push(format!("event_type IN ({})", placeholders.join(", ")));
+        }
+    }
+    if let Some(filter) = &query.process_filter {
+        if let Some(pid) = filter.pid {
+            binds.push(Value::Integer(pid as i64));
+            clauses.push(format!("pid = ?{}", binds.len()));
+        }
+        if let Some(comm) = &filter.comm {
+            binds.push(Value::Text(comm.clone()));
+            clauses.push(format!("comm = ?{}", binds.len()));
+        }
+        if let Some(uid) = filter.uid {
+            binds.push(Value::Integer(uid as i64));
+            clauses.push(format!("uid = ?{}", binds.len()));
+        }
+    }
+
+    if !clauses.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&clauses.join(" AND "));
+    }
+    sql.push_str(" ORDER BY timestamp DESC");
+    if let Some(limit) = query.limit {
+        binds.push(Value::Integer(limit as i64));
+        sql.push_str(&format!(" LIMIT ?{}", binds.len()));
+    }
+
+    (sql, binds)
+}
+
+fn row_to_event(row: &rusqlite::Row) -> rusqlite::Result<Event> {
+    let data: String = row.get(10)?;
+    Ok(Event {
+        id: row.get(0)?,
+        timestamp: row.get::<_, i64>(1)? as u64,
+        event_type: row.get(2)?,
+        process: ProcessInfo {
+            pid: row.get(3)?,
+            ppid: row.get(4)?,
+            uid: row.get(5)?,
+            gid: row.get(6)?,
+            comm: row.get(7)?,
+            exe: row.get(8)?,
+            start_time: row.get::<_, i64>(9)? as u64,
+        },
+        data: serde_json::from_str(&data).unwrap_or(serde_json::Value::Null),
+    })
+}
+
+fn map_err(e: rusqlite::Error) -> EventError {
+    EventError::StorageError(e.to_string())
+}
+
+#[async_trait::async_trait]
+impl crate::EventStorage for SqliteStorage {
+    async fn store(&self, event: &Event) -> Result<(), EventError> {
+        let storage = self.clone();
+        let event = event.clone();
+        tokio::task::spawn_blocking(move || storage.store_batch(std::slice::from_ref(&event)))
+            .await
+            .map_err(|e| EventError::StorageError(e.to_string()))?
+    }
+
+    async fn query(&self, query: &EventQuery) -> Result<Vec<Event>, EventError> {
+        let storage = self.clone();
+        let query = query.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = storage.conn()?;
+            let (sql, binds) = build_query(&query);
+            let mut stmt = conn.prepare(&sql).map_err(map_err)?;
+            let rows = stmt
+                .query_map(params_from_iter(binds), row_to_event)
+                .map_err(map_err)?;
+            rows.collect::<rusqlite::Result<Vec<_>>>().map_err(map_err)
+        })
+        .await
+        .map_err(|e| EventError::StorageError(e.to_string()))?
+    }
+}